@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Writes `samples` (interleaved, `channels` wide) to `path` as a canonical
+/// 32-bit IEEE-float WAV file: a `RIFF`/`fmt `/`data` chunk layout with block
+/// alignment derived from `channels` and byte rate from `sample_rate`.
+pub fn write_wav_f32(
+    path: impl AsRef<Path>,
+    samples: &[f32],
+    channels: u16,
+    sample_rate: u32,
+) -> io::Result<()> {
+    const BITS_PER_SAMPLE: u16 = 32;
+    const AUDIO_FORMAT_IEEE_FLOAT: u16 = 3;
+
+    let block_align = channels * (BITS_PER_SAMPLE / 8);
+    let byte_rate = sample_rate * block_align as u32;
+    let data_size = (samples.len() * (BITS_PER_SAMPLE as usize / 8)) as u32;
+    let riff_size = 4 + (8 + 16) + (8 + data_size);
+
+    let mut w = BufWriter::new(File::create(path)?);
+
+    w.write_all(b"RIFF")?;
+    w.write_all(&riff_size.to_le_bytes())?;
+    w.write_all(b"WAVE")?;
+
+    w.write_all(b"fmt ")?;
+    w.write_all(&16u32.to_le_bytes())?;
+    w.write_all(&AUDIO_FORMAT_IEEE_FLOAT.to_le_bytes())?;
+    w.write_all(&channels.to_le_bytes())?;
+    w.write_all(&sample_rate.to_le_bytes())?;
+    w.write_all(&byte_rate.to_le_bytes())?;
+    w.write_all(&block_align.to_le_bytes())?;
+    w.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+    w.write_all(b"data")?;
+    w.write_all(&data_size.to_le_bytes())?;
+    for sample in samples {
+        w.write_all(&sample.to_le_bytes())?;
+    }
+
+    w.flush()
+}
+
+/// Encodes `samples` (interleaved, `channels` wide) to `path` as FLAC,
+/// quantizing to 24-bit PCM since FLAC is an integer codec.
+#[cfg(feature = "flac")]
+pub fn write_flac_f32(
+    path: impl AsRef<Path>,
+    samples: &[f32],
+    channels: u32,
+    sample_rate: u32,
+) -> io::Result<()> {
+    use flac_bound::FlacEncoder;
+
+    const BITS_PER_SAMPLE: u32 = 24;
+    let max_val = (1i64 << (BITS_PER_SAMPLE - 1)) as f32 - 1.0;
+    let quantized: Vec<i32> = samples
+        .iter()
+        .map(|s| (s.clamp(-1.0, 1.0) * max_val) as i32)
+        .collect();
+
+    let mut encoder = FlacEncoder::new()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "failed to allocate FLAC encoder"))?
+        .channels(channels)
+        .bits_per_sample(BITS_PER_SAMPLE)
+        .sample_rate(sample_rate)
+        .init_file(path.as_ref())
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to initialize FLAC encoder"))?;
+
+    encoder
+        .process_interleaved(&quantized, quantized.len() as u32 / channels)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "FLAC encode failed"))?;
+    encoder
+        .finish()
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "FLAC finalize failed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// Minimal WAV reader used only to verify what `write_wav_f32` produced.
+    fn read_wav_f32(path: impl AsRef<Path>) -> (u16, u32, Vec<f32>) {
+        let mut bytes = Vec::new();
+        File::open(path).unwrap().read_to_end(&mut bytes).unwrap();
+
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[8..12], b"WAVE");
+        assert_eq!(&bytes[12..16], b"fmt ");
+
+        let channels = u16::from_le_bytes([bytes[22], bytes[23]]);
+        let sample_rate = u32::from_le_bytes([bytes[24], bytes[25], bytes[26], bytes[27]]);
+
+        assert_eq!(&bytes[36..40], b"data");
+        let data_size = u32::from_le_bytes([bytes[40], bytes[41], bytes[42], bytes[43]]) as usize;
+        let samples = bytes[44..44 + data_size]
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+
+        (channels, sample_rate, samples)
+    }
+
+    #[test]
+    fn wav_round_trip() {
+        let path = std::env::temp_dir().join("condenser_rs_export_test.wav");
+        let samples = vec![0.0, 0.5, -0.5, 1.0, -1.0, 0.25];
+        write_wav_f32(&path, &samples, 2, 48000).unwrap();
+
+        let (channels, sample_rate, read_back) = read_wav_f32(&path);
+        assert_eq!(channels, 2);
+        assert_eq!(sample_rate, 48000);
+        assert_eq!(read_back, samples);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -2,7 +2,9 @@ use nih_plug::prelude::*;
 use std::sync::Arc;
 
 mod condenser;
-use condenser::Condenser;
+mod export;
+mod scope;
+use condenser::{Condenser, CondenserConfig};
 
 const TH_DB: f32 = -40.0;
 const DRY_WET: f32 = 0.5;
@@ -11,6 +13,13 @@ const REL_MS: f32 = 50.0;
 const RING_SEC: i32 = 60;
 const WARMUP_S: f32 = 0.3;
 const LOOP_MODE: bool = false;
+const LOUDNESS_GATE: bool = false;
+const THRESHOLD_LUFS: f32 = -23.0;
+const SNAP_TO_TRANSIENT: bool = false;
+const ONSET_SENSITIVITY: f32 = 1.5;
+const PLAY_RATE: f32 = 1.0;
+const TP_ENABLED: bool = false;
+const TP_CEILING_DB: f32 = -1.0;
 
 // This is a shortened version of the gain example with most comments removed, check out
 // https://github.com/robbert-vdh/nih-plug/blob/master/plugins/examples/gain/src/lib.rs to get
@@ -20,6 +29,20 @@ struct CondenserRs {
     params: Arc<CondenserRsParams>,
     fx_l: Option<Condenser>,
     fx_r: Option<Condenser>,
+    export_now_prev: bool,
+    // Read by a future editor to draw per-channel metering/waveform views.
+    scope_handle_l: Option<scope::ScopeHandle>,
+    scope_handle_r: Option<scope::ScopeHandle>,
+}
+
+/// Work dispatched to the background thread so disk I/O never happens on the
+/// audio thread.
+#[derive(Debug, Clone)]
+pub enum CondenserRsTask {
+    ExportRecording {
+        interleaved: Vec<f32>,
+        sample_rate: u32,
+    },
 }
 
 #[derive(Params)]
@@ -44,6 +67,30 @@ struct CondenserRsParams {
 
     #[id = "loop_mode"]
     pub loop_mode: BoolParam,
+
+    #[id = "loudness_gate"]
+    pub loudness_gate: BoolParam,
+
+    #[id = "threshold_lufs"]
+    pub threshold_lufs: FloatParam,
+
+    #[id = "snap_to_transient"]
+    pub snap_to_transient: BoolParam,
+
+    #[id = "onset_sensitivity"]
+    pub onset_sensitivity: FloatParam,
+
+    #[id = "export_now"]
+    pub export_now: BoolParam,
+
+    #[id = "play_rate"]
+    pub play_rate: FloatParam,
+
+    #[id = "tp_enabled"]
+    pub tp_enabled: BoolParam,
+
+    #[id = "tp_ceiling_db"]
+    pub tp_ceiling_db: FloatParam,
 }
 
 impl Default for CondenserRs {
@@ -52,6 +99,9 @@ impl Default for CondenserRs {
             params: Arc::new(CondenserRsParams::default()),
             fx_l: None,
             fx_r: None,
+            export_now_prev: false,
+            scope_handle_l: None,
+            scope_handle_r: None,
         }
     }
 }
@@ -109,6 +159,46 @@ impl Default for CondenserRsParams {
             .with_unit(" s"),
 
             loop_mode: BoolParam::new("Loop Mode", LOOP_MODE),
+
+            loudness_gate: BoolParam::new("Loudness Gate", LOUDNESS_GATE),
+
+            threshold_lufs: FloatParam::new(
+                "Threshold (LUFS)",
+                THRESHOLD_LUFS,
+                FloatRange::Linear {
+                    min: -60.0,
+                    max: 0.0,
+                },
+            )
+            .with_unit(" LUFS"),
+
+            snap_to_transient: BoolParam::new("Snap To Transient", SNAP_TO_TRANSIENT),
+
+            onset_sensitivity: FloatParam::new(
+                "Onset Sensitivity",
+                ONSET_SENSITIVITY,
+                FloatRange::Linear { min: 1.05, max: 4.0 },
+            ),
+
+            export_now: BoolParam::new("Export Now", false),
+
+            play_rate: FloatParam::new(
+                "Play Rate",
+                PLAY_RATE,
+                FloatRange::Linear { min: 0.25, max: 4.0 },
+            ),
+
+            tp_enabled: BoolParam::new("True Peak Limiter", TP_ENABLED),
+
+            tp_ceiling_db: FloatParam::new(
+                "True Peak Ceiling",
+                TP_CEILING_DB,
+                FloatRange::Linear {
+                    min: -6.0,
+                    max: 0.0,
+                },
+            )
+            .with_unit(" dBTP"),
         }
     }
 }
@@ -145,10 +235,39 @@ impl Plugin for CondenserRs {
     // messages here. The type implements the `SysExMessage` trait, which allows conversion to and
     // from plain byte buffers.
     type SysExMessage = ();
-    // More advanced plugins can use this to run expensive background tasks. See the field's
-    // documentation for more information. `()` means that the plugin does not have any background
-    // tasks.
-    type BackgroundTask = ();
+    // Exporting the recorded loop to disk happens here so the write never blocks the audio
+    // thread.
+    type BackgroundTask = CondenserRsTask;
+
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        Box::new(|task| match task {
+            CondenserRsTask::ExportRecording {
+                interleaved,
+                sample_rate,
+            } => {
+                // With the `flac` feature enabled, archive to lossless FLAC instead
+                // of the default WAV so long loops don't eat disk space.
+                #[cfg(feature = "flac")]
+                let result = export::write_flac_f32(
+                    "condenser_rs_export.flac",
+                    &interleaved,
+                    2,
+                    sample_rate,
+                );
+                #[cfg(not(feature = "flac"))]
+                let result = export::write_wav_f32(
+                    "condenser_rs_export.wav",
+                    &interleaved,
+                    2,
+                    sample_rate,
+                );
+
+                if let Err(err) = result {
+                    nih_error!("Failed to export recording: {err}");
+                }
+            }
+        })
+    }
 
     fn params(&self) -> Arc<dyn Params> {
         self.params.clone()
@@ -162,65 +281,109 @@ impl Plugin for CondenserRs {
     ) -> bool {
         let fs = buffer_config.sample_rate as usize;
         let p = &self.params;
-        self.fx_l = Some(Condenser::new(
+        self.fx_l = Some(Condenser::new(CondenserConfig {
             fs,
-            p.threshold_db.value(),
-            p.dry_wet.value(),
-            p.fade_ms.value(),
-            p.rel_ms.value(),
-            p.ring_sec.value() as usize,
-            p.warmup_s.value(),
-            p.loop_mode.value(),
-        ));
-        self.fx_r = Some(Condenser::new(
+            threshold_db: p.threshold_db.value(),
+            dry_wet: p.dry_wet.value(),
+            fade_ms: p.fade_ms.value(),
+            rel_ms: p.rel_ms.value(),
+            max_seconds: p.ring_sec.value() as usize,
+            warmup_sec: p.warmup_s.value(),
+            loop_mode: p.loop_mode.value(),
+            loudness_gate: p.loudness_gate.value(),
+            threshold_lufs: p.threshold_lufs.value(),
+            snap_to_transient: p.snap_to_transient.value(),
+            onset_ratio: p.onset_sensitivity.value(),
+            play_rate: p.play_rate.value(),
+            tp_enabled: p.tp_enabled.value(),
+            tp_ceiling_db: p.tp_ceiling_db.value(),
+        }));
+        self.fx_r = Some(Condenser::new(CondenserConfig {
             fs,
-            p.threshold_db.value(),
-            p.dry_wet.value(),
-            p.fade_ms.value(),
-            p.rel_ms.value(),
-            p.ring_sec.value() as usize,
-            p.warmup_s.value(),
-            p.loop_mode.value(),
-        ));
+            threshold_db: p.threshold_db.value(),
+            dry_wet: p.dry_wet.value(),
+            fade_ms: p.fade_ms.value(),
+            rel_ms: p.rel_ms.value(),
+            max_seconds: p.ring_sec.value() as usize,
+            warmup_sec: p.warmup_s.value(),
+            loop_mode: p.loop_mode.value(),
+            loudness_gate: p.loudness_gate.value(),
+            threshold_lufs: p.threshold_lufs.value(),
+            snap_to_transient: p.snap_to_transient.value(),
+            onset_ratio: p.onset_sensitivity.value(),
+            play_rate: p.play_rate.value(),
+            tp_enabled: p.tp_enabled.value(),
+            tp_ceiling_db: p.tp_ceiling_db.value(),
+        }));
+        self.scope_handle_l = self.fx_l.as_ref().map(Condenser::scope_handle);
+        self.scope_handle_r = self.fx_r.as_ref().map(Condenser::scope_handle);
         true
     }
 
     fn reset(&mut self) {
         let p = &self.params;
         if let Some(fx) = &mut self.fx_l {
-            *fx = Condenser::new(
-                fx.fs,
-                p.threshold_db.value(),
-                p.dry_wet.value(),
-                p.fade_ms.value(),
-                p.rel_ms.value(),
-                p.ring_sec.value() as usize,
-                p.warmup_s.value(),
-                p.loop_mode.value(),
-            );
+            *fx = Condenser::new(CondenserConfig {
+                fs: fx.fs,
+                threshold_db: p.threshold_db.value(),
+                dry_wet: p.dry_wet.value(),
+                fade_ms: p.fade_ms.value(),
+                rel_ms: p.rel_ms.value(),
+                max_seconds: p.ring_sec.value() as usize,
+                warmup_sec: p.warmup_s.value(),
+                loop_mode: p.loop_mode.value(),
+                loudness_gate: p.loudness_gate.value(),
+                threshold_lufs: p.threshold_lufs.value(),
+                snap_to_transient: p.snap_to_transient.value(),
+                onset_ratio: p.onset_sensitivity.value(),
+                play_rate: p.play_rate.value(),
+                tp_enabled: p.tp_enabled.value(),
+                tp_ceiling_db: p.tp_ceiling_db.value(),
+            });
         }
         if let Some(fx) = &mut self.fx_r {
-            *fx = Condenser::new(
-                fx.fs,
-                p.threshold_db.value(),
-                p.dry_wet.value(),
-                p.fade_ms.value(),
-                p.rel_ms.value(),
-                p.ring_sec.value() as usize,
-                p.warmup_s.value(),
-                p.loop_mode.value(),
-            );
+            *fx = Condenser::new(CondenserConfig {
+                fs: fx.fs,
+                threshold_db: p.threshold_db.value(),
+                dry_wet: p.dry_wet.value(),
+                fade_ms: p.fade_ms.value(),
+                rel_ms: p.rel_ms.value(),
+                max_seconds: p.ring_sec.value() as usize,
+                warmup_sec: p.warmup_s.value(),
+                loop_mode: p.loop_mode.value(),
+                loudness_gate: p.loudness_gate.value(),
+                threshold_lufs: p.threshold_lufs.value(),
+                snap_to_transient: p.snap_to_transient.value(),
+                onset_ratio: p.onset_sensitivity.value(),
+                play_rate: p.play_rate.value(),
+                tp_enabled: p.tp_enabled.value(),
+                tp_ceiling_db: p.tp_ceiling_db.value(),
+            });
         }
+        self.scope_handle_l = self.fx_l.as_ref().map(Condenser::scope_handle);
+        self.scope_handle_r = self.fx_r.as_ref().map(Condenser::scope_handle);
     }
 
     fn process(
         &mut self,
         buffer: &mut Buffer,
         _aux: &mut AuxiliaryBuffers,
-        _context: &mut impl ProcessContext<Self>,
+        context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
         let channels = buffer.as_slice();
         let p = &self.params;
+
+        let export_now = p.export_now.value();
+        if export_now && !self.export_now_prev {
+            if let (Some(fx_l), Some(fx_r)) = (&self.fx_l, &self.fx_r) {
+                context.execute_background(CondenserRsTask::ExportRecording {
+                    interleaved: fx_l.export_interleaved(fx_r),
+                    sample_rate: fx_l.fs as u32,
+                });
+            }
+        }
+        self.export_now_prev = export_now;
+
         if let Some(fx) = &mut self.fx_l {
             fx.set_threshold_db(p.threshold_db.value());
             fx.set_dry_wet(p.dry_wet.value());
@@ -229,6 +392,13 @@ impl Plugin for CondenserRs {
             fx.set_ring_sec(p.ring_sec.value() as usize);
             fx.set_warmup_sec(p.warmup_s.value());
             fx.set_loop_mode(p.loop_mode.value());
+            fx.set_loudness_gate(p.loudness_gate.value());
+            fx.set_threshold_lufs(p.threshold_lufs.value());
+            fx.set_snap_to_transient(p.snap_to_transient.value());
+            fx.set_onset_sensitivity(p.onset_sensitivity.value());
+            fx.set_play_rate(p.play_rate.value());
+            fx.set_tp_enabled(p.tp_enabled.value());
+            fx.set_tp_ceiling_db(p.tp_ceiling_db.value());
 
             if let Some(ch) = channels.get_mut(0) {
                 fx.process_inplace(*ch);
@@ -242,6 +412,13 @@ impl Plugin for CondenserRs {
             fx.set_ring_sec(p.ring_sec.value() as usize);
             fx.set_warmup_sec(p.warmup_s.value());
             fx.set_loop_mode(p.loop_mode.value());
+            fx.set_loudness_gate(p.loudness_gate.value());
+            fx.set_threshold_lufs(p.threshold_lufs.value());
+            fx.set_snap_to_transient(p.snap_to_transient.value());
+            fx.set_onset_sensitivity(p.onset_sensitivity.value());
+            fx.set_play_rate(p.play_rate.value());
+            fx.set_tp_enabled(p.tp_enabled.value());
+            fx.set_tp_ceiling_db(p.tp_ceiling_db.value());
 
             if let Some(ch) = channels.get_mut(1) {
                 fx.process_inplace(*ch);
@@ -0,0 +1,82 @@
+use std::sync::{Arc, Mutex};
+
+use crate::condenser::State;
+
+/// Number of min/max bins kept in the scrolling waveform history.
+pub const SCOPE_BINS: usize = 128;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScopeBin {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// One published view of what a `Condenser` is doing, for a GUI to poll.
+#[derive(Debug, Clone)]
+pub struct ScopeSnapshot {
+    pub state: State,
+    pub env: f32,
+    pub fill_fraction: f32,
+    pub bins: Vec<ScopeBin>,
+}
+
+impl Default for ScopeSnapshot {
+    fn default() -> Self {
+        Self {
+            state: State::Idle,
+            env: 0.0,
+            fill_fraction: 0.0,
+            bins: vec![ScopeBin::default(); SCOPE_BINS],
+        }
+    }
+}
+
+/// Consumer side: cheap to clone, safe to poll from a GUI thread.
+#[derive(Clone)]
+pub struct ScopeHandle {
+    output: Arc<Mutex<triple_buffer::Output<ScopeSnapshot>>>,
+}
+
+impl ScopeHandle {
+    pub fn read(&self) -> ScopeSnapshot {
+        self.output.lock().unwrap().read().clone()
+    }
+}
+
+/// Producer side: owned by a `Condenser`, written to from `process_inplace`.
+/// Mutates the back buffer in place so pushing a block never allocates.
+pub struct ScopeWriter {
+    input: triple_buffer::Input<ScopeSnapshot>,
+}
+
+impl ScopeWriter {
+    pub fn push_block(&mut self, block: &[f32], state: State, env: f32, fill_fraction: f32) {
+        let Some((&first, rest)) = block.split_first() else {
+            return;
+        };
+        let (min, max) = rest
+            .iter()
+            .fold((first, first), |(min, max), &s| (min.min(s), max.max(s)));
+
+        let snapshot = self.input.input_buffer();
+        snapshot.bins.rotate_left(1);
+        let last = snapshot.bins.len() - 1;
+        snapshot.bins[last] = ScopeBin { min, max };
+        snapshot.state = state;
+        snapshot.env = env;
+        snapshot.fill_fraction = fill_fraction;
+
+        self.input.publish();
+    }
+}
+
+/// Builds a connected writer/handle pair sharing one triple buffer.
+pub fn scope_pair() -> (ScopeWriter, ScopeHandle) {
+    let (input, output) = triple_buffer::TripleBuffer::new(&ScopeSnapshot::default()).split();
+    (
+        ScopeWriter { input },
+        ScopeHandle {
+            output: Arc::new(Mutex::new(output)),
+        },
+    )
+}